@@ -0,0 +1,6 @@
+/// An opaque snapshot of scanner progress, captured with
+/// `Scanner::checkpoint` and later rewound to with `Scanner::restore`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Checkpoint {
+    pub(crate) cursor: usize,
+}
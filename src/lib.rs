@@ -1,7 +1,13 @@
 mod action;
+mod checkpoint;
 mod error;
+mod position;
 mod scanner;
+mod spanned;
 
 pub use action::Action;
+pub use checkpoint::Checkpoint;
 pub use error::Error;
+pub use position::Position;
 pub use scanner::Scanner;
+pub use spanned::Spanned;
@@ -0,0 +1,13 @@
+/// A location within the scanned input, given as a flat character offset
+/// and the equivalent 1-indexed line and column.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    /// The character offset from the start of the input.
+    pub offset: usize,
+
+    /// The 1-indexed line number.
+    pub line: usize,
+
+    /// The 1-indexed column number.
+    pub column: usize,
+}
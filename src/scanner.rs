@@ -1,4 +1,4 @@
-use crate::{Action, Error};
+use crate::{Action, Checkpoint, Error, Position, Spanned};
 
 /// A tool for processing the characters in a string individually and
 /// in groups with only one character of lookahead.
@@ -6,13 +6,22 @@ use crate::{Action, Error};
 pub struct Scanner {
     cursor: usize,
     characters: Vec<char>,
+    newlines: Vec<usize>,
 }
 
 impl Scanner {
     pub fn new(string: &str) -> Self {
+        let characters: Vec<char> = string.chars().collect();
+        let newlines = characters
+            .iter()
+            .enumerate()
+            .filter_map(|(index, character)| (*character == '\n').then_some(index))
+            .collect();
+
         Self {
             cursor: 0,
-            characters: string.chars().collect(),
+            characters,
+            newlines,
         }
     }
 
@@ -21,12 +30,71 @@ impl Scanner {
         self.cursor
     }
 
+    /// Returns the line and column of the current cursor, in addition to
+    /// its flat offset. Useful for reporting errors in multi-line input.
+    pub fn position(&self) -> Position {
+        let (line, column) = self.line_column(self.cursor);
+
+        Position {
+            offset: self.cursor,
+            line,
+            column,
+        }
+    }
+
+    /// Captures the current cursor so that scanning can later be rewound
+    /// to this point with `restore`.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            cursor: self.cursor,
+        }
+    }
+
+    /// Rewinds the cursor to a `checkpoint` previously captured with
+    /// `checkpoint`.
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        self.cursor = checkpoint.cursor;
+    }
+
+    /// Runs `cb`, committing its cursor advance if it returns `Some` and
+    /// restoring the cursor to where it was before `cb` ran otherwise.
+    pub fn attempt<T>(
+        &mut self,
+        cb: impl FnOnce(&mut Scanner) -> Option<T>,
+    ) -> Option<T> {
+        let checkpoint = self.checkpoint();
+        let result = cb(self);
+
+        if result.is_none() {
+            self.restore(checkpoint);
+        }
+
+        result
+    }
+
+    /// Returns the 1-indexed line and column corresponding to `offset`,
+    /// derived from the offsets of newlines already present in the input.
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        match self.newlines.partition_point(|&newline| newline < offset) {
+            0 => (1, offset + 1),
+            index => (index + 1, offset - self.newlines[index - 1]),
+        }
+    }
+
     /// Returns the next character without advancing the cursor.
     /// AKA "lookahead"
     pub fn peek(&self) -> Option<&char> {
         self.characters.get(self.cursor)
     }
 
+    /// Returns up to the next `n` characters without advancing the cursor.
+    /// The returned slice is shorter than `n` if fewer characters remain.
+    pub fn peek_n(&self, n: usize) -> &[char] {
+        let end = (self.cursor + n).min(self.characters.len());
+
+        &self.characters[self.cursor..end]
+    }
+
     /// Returns true if further progress is not possible.
     pub fn is_done(&self) -> bool {
         self.cursor == self.characters.len()
@@ -53,39 +121,66 @@ impl Scanner {
         }
     }
 
+    /// Returns true if `target` is found in full at the current cursor
+    /// position, and advances the cursor past it.
+    /// Otherwise, returns false leaving the cursor unchanged.
+    pub fn take_str(&mut self, target: &str) -> bool {
+        let target: Vec<char> = target.chars().collect();
+
+        if self.peek_n(target.len()) == target.as_slice() {
+            self.cursor += target.len();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Advances the cursor past the longest run of characters (possibly
+    /// empty) satisfying `pred`, and returns the consumed characters.
+    pub fn take_while(&mut self, pred: impl Fn(&char) -> bool) -> &[char] {
+        let start = self.cursor;
+
+        while self.characters.get(self.cursor).map_or(false, &pred) {
+            self.cursor += 1;
+        }
+
+        &self.characters[start..self.cursor]
+    }
+
     /// Iteratively directs the advancement of the cursor and the return
     /// of translated values.
     pub fn scan<T>(
         &mut self,
-        cb: impl Fn(&str) -> Option<Action<T>>,
+        mut cb: impl FnMut(&[char]) -> Option<Action<T>>,
     ) -> Result<Option<T>, Error> {
-        let mut sequence = String::new();
+        let start = self.cursor;
         let mut require = false;
         let mut request = None;
 
         loop {
             match self.characters.get(self.cursor) {
-                Some(target) => {
-                    sequence.push(*target);
+                Some(_) => {
+                    let end = self.cursor + 1;
 
-                    match cb(&sequence) {
+                    match cb(&self.characters[start..end]) {
                         Some(Action::Return(result)) => {
-                            self.cursor += 1;
+                            self.cursor = end;
 
                             break Ok(Some(result));
                         }
                         Some(Action::Request(result)) => {
-                            self.cursor += 1;
+                            self.cursor = end;
                             require = false;
                             request = Some(result);
                         }
                         Some(Action::Require) => {
-                            self.cursor += 1;
+                            self.cursor = end;
                             require = true;
                         }
                         None => {
                             if require {
-                                break Err(Error::Character(self.cursor));
+                                break Err(Error::Character(self.position()));
                             } else {
                                 break Ok(request);
                             }
@@ -103,6 +198,34 @@ impl Scanner {
         }
     }
 
+    /// Repeatedly drives `rule` until the input is exhausted, collecting
+    /// each non-`None` result into a `Spanned` token carrying the cursor
+    /// position before and after `rule` ran. A `rule` that returns `None`
+    /// without advancing the cursor stops the loop, guarding against
+    /// infinite loops on rules that never match and never error.
+    pub fn tokenize<T>(
+        &mut self,
+        mut rule: impl FnMut(&mut Scanner) -> Result<Option<T>, Error>,
+    ) -> Result<Vec<Spanned<T>>, Error> {
+        let mut tokens = Vec::new();
+
+        while !self.is_done() {
+            let start = self.position();
+
+            match rule(self)? {
+                Some(value) => tokens.push(Spanned {
+                    value,
+                    start,
+                    end: self.position(),
+                }),
+                None if self.position() == start => break,
+                None => {}
+            }
+        }
+
+        Ok(tokens)
+    }
+
     /// Invoke `cb` once. If the result is not `None`, return it and advance
     /// the cursor. Otherwise, return None and leave the cursor unchanged.
     pub fn transform<T>(
@@ -150,6 +273,144 @@ mod cursor {
     }
 }
 
+#[cfg(test)]
+mod position {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let scanner = Scanner::new("");
+
+        assert_eq!(
+            scanner.position(),
+            Position {
+                offset: 0,
+                line: 1,
+                column: 1
+            }
+        )
+    }
+
+    #[test]
+    fn single_line() {
+        let mut scanner = Scanner::new("abc");
+
+        scanner.pop();
+        scanner.pop();
+
+        assert_eq!(
+            scanner.position(),
+            Position {
+                offset: 2,
+                line: 1,
+                column: 3
+            }
+        )
+    }
+
+    #[test]
+    fn after_newline() {
+        let mut scanner = Scanner::new("ab\ncd");
+
+        for _ in 0..4 {
+            scanner.pop();
+        }
+
+        assert_eq!(
+            scanner.position(),
+            Position {
+                offset: 4,
+                line: 2,
+                column: 2
+            }
+        )
+    }
+
+    #[test]
+    fn multiple_newlines() {
+        let mut scanner = Scanner::new("a\nbb\nccc");
+
+        for _ in 0..7 {
+            scanner.pop();
+        }
+
+        assert_eq!(
+            scanner.position(),
+            Position {
+                offset: 7,
+                line: 3,
+                column: 3
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod checkpoint {
+    use super::*;
+
+    #[test]
+    fn captures_cursor() {
+        let mut scanner = Scanner::new("abc");
+
+        scanner.pop();
+
+        assert_eq!(scanner.checkpoint(), Checkpoint { cursor: 1 })
+    }
+}
+
+#[cfg(test)]
+mod restore {
+    use super::*;
+
+    #[test]
+    fn rewinds_cursor() {
+        let mut scanner = Scanner::new("abc");
+        let checkpoint = scanner.checkpoint();
+
+        scanner.pop();
+        scanner.pop();
+        scanner.restore(checkpoint);
+
+        assert_eq!(scanner.cursor(), 0)
+    }
+}
+
+#[cfg(test)]
+mod attempt {
+    use super::*;
+
+    #[test]
+    fn commits_on_some() {
+        let mut scanner = Scanner::new("abc");
+
+        let result = scanner.attempt(|scanner| {
+            scanner.pop();
+            scanner.pop();
+
+            Some(())
+        });
+
+        assert_eq!(result, Some(()));
+        assert_eq!(scanner.cursor(), 2)
+    }
+
+    #[test]
+    fn restores_on_none() {
+        let mut scanner = Scanner::new("abc");
+
+        let result: Option<()> = scanner.attempt(|scanner| {
+            scanner.pop();
+            scanner.pop();
+
+            None
+        });
+
+        assert_eq!(result, None);
+        assert_eq!(scanner.cursor(), 0)
+    }
+}
+
 #[cfg(test)]
 mod is_done {
     use super::*;
@@ -203,6 +464,39 @@ mod peek {
     }
 }
 
+#[cfg(test)]
+mod peek_n {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let scanner = Scanner::new("");
+
+        assert_eq!(scanner.peek_n(2), &[] as &[char])
+    }
+
+    #[test]
+    fn not_done() {
+        let scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.peek_n(2), &['a', 'b'])
+    }
+
+    #[test]
+    fn clamped_at_end() {
+        let scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.peek_n(10), &['a', 'b', 'c'])
+    }
+
+    #[test]
+    fn zero() {
+        let scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.peek_n(0), &[] as &[char])
+    }
+}
+
 #[cfg(test)]
 mod pop {
     use super::*;
@@ -267,6 +561,80 @@ mod take {
     }
 }
 
+#[cfg(test)]
+mod take_str {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut scanner = Scanner::new("");
+
+        assert_eq!(scanner.take_str("ab"), false);
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn unmatched() {
+        let mut scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.take_str("ac"), false);
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn partial_match() {
+        let mut scanner = Scanner::new("ab");
+
+        assert_eq!(scanner.take_str("abc"), false);
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn matched() {
+        let mut scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.take_str("ab"), true);
+        assert_eq!(scanner.cursor(), 2)
+    }
+}
+
+#[cfg(test)]
+mod take_while {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let mut scanner = Scanner::new("");
+
+        assert_eq!(scanner.take_while(|c| c.is_alphabetic()), &[] as &[char]);
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn no_match() {
+        let mut scanner = Scanner::new("123");
+
+        assert_eq!(scanner.take_while(|c| c.is_alphabetic()), &[] as &[char]);
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn partial_run() {
+        let mut scanner = Scanner::new("ab12");
+
+        assert_eq!(scanner.take_while(|c| c.is_alphabetic()), &['a', 'b']);
+        assert_eq!(scanner.cursor(), 2)
+    }
+
+    #[test]
+    fn full_run() {
+        let mut scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.take_while(|c| c.is_alphabetic()), &['a', 'b', 'c']);
+        assert_eq!(scanner.cursor(), 3)
+    }
+}
+
 #[cfg(test)]
 mod scan {
     use super::*;
@@ -290,7 +658,7 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Return(())),
+                ['a'] => Some(Action::Return(())),
                 _ => unreachable!(),
             }),
             Ok(Some(()))
@@ -304,10 +672,14 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Require),
+                ['a'] => Some(Action::Require),
                 _ => None,
             } as Option<Action<()>>),
-            Err(Error::Character(1))
+            Err(Error::Character(Position {
+                offset: 1,
+                line: 1,
+                column: 2
+            }))
         );
         assert_eq!(scanner.cursor(), 1)
     }
@@ -320,8 +692,8 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "b" => Some(Action::Require),
-                "bc" => Some(Action::Require),
+                ['b'] => Some(Action::Require),
+                ['b', 'c'] => Some(Action::Require),
                 _ => None,
             } as Option<Action<()>>),
             Err(Error::EndOfLine)
@@ -335,9 +707,9 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Require),
-                "ab" => Some(Action::Require),
-                "abc" => Some(Action::Return(())),
+                ['a'] => Some(Action::Require),
+                ['a', 'b'] => Some(Action::Require),
+                ['a', 'b', 'c'] => Some(Action::Return(())),
                 _ => None,
             }),
             Ok(Some(()))
@@ -351,8 +723,8 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Require),
-                "ab" => Some(Action::Request(())),
+                ['a'] => Some(Action::Require),
+                ['a', 'b'] => Some(Action::Request(())),
                 _ => None,
             }),
             Ok(Some(()))
@@ -366,7 +738,7 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Request(())),
+                ['a'] => Some(Action::Request(())),
                 _ => None,
             }),
             Ok(Some(()))
@@ -383,7 +755,7 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "c" => Some(Action::Request(())),
+                ['c'] => Some(Action::Request(())),
                 _ => None,
             }),
             Ok(Some(()))
@@ -397,8 +769,8 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Request(1)),
-                "ab" => Some(Action::Return(2)),
+                ['a'] => Some(Action::Request(1)),
+                ['a', 'b'] => Some(Action::Return(2)),
                 _ => unreachable!(),
             }),
             Ok(Some(2))
@@ -412,11 +784,15 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Request(1)),
-                "ab" => Some(Action::Require),
+                ['a'] => Some(Action::Request(1)),
+                ['a', 'b'] => Some(Action::Require),
                 _ => None,
             }),
-            Err(Error::Character(2))
+            Err(Error::Character(Position {
+                offset: 2,
+                line: 1,
+                column: 3
+            }))
         );
         assert_eq!(scanner.cursor(), 2)
     }
@@ -427,9 +803,9 @@ mod scan {
 
         assert_eq!(
             scanner.scan(|sequence| match sequence {
-                "a" => Some(Action::Request(1)),
-                "ab" => Some(Action::Require),
-                "abc" => Some(Action::Return(2)),
+                ['a'] => Some(Action::Request(1)),
+                ['a', 'b'] => Some(Action::Require),
+                ['a', 'b', 'c'] => Some(Action::Return(2)),
                 _ => None,
             }),
             Ok(Some(2))
@@ -465,3 +841,119 @@ mod transform {
         assert_eq!(scanner.transform(|_| Some(1)), Some(1))
     }
 }
+
+#[cfg(test)]
+mod tokenize {
+    use super::*;
+
+    fn digits(scanner: &mut Scanner) -> Result<Option<u32>, Error> {
+        let digits = scanner.take_while(|c| c.is_ascii_digit());
+
+        if digits.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(digits.iter().collect::<String>().parse().unwrap()))
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut scanner = Scanner::new("");
+
+        assert_eq!(scanner.tokenize(digits), Ok(vec![]))
+    }
+
+    #[test]
+    fn no_match_stops_without_progress() {
+        let mut scanner = Scanner::new("abc");
+
+        assert_eq!(scanner.tokenize(digits), Ok(vec![]));
+        assert_eq!(scanner.cursor(), 0)
+    }
+
+    #[test]
+    fn single_token() {
+        let mut scanner = Scanner::new("123");
+
+        assert_eq!(
+            scanner.tokenize(digits),
+            Ok(vec![Spanned {
+                value: 123,
+                start: Position {
+                    offset: 0,
+                    line: 1,
+                    column: 1
+                },
+                end: Position {
+                    offset: 3,
+                    line: 1,
+                    column: 4
+                }
+            }])
+        )
+    }
+
+    #[test]
+    fn multiple_tokens() {
+        let mut scanner = Scanner::new("12,34");
+
+        let rule = |scanner: &mut Scanner| {
+            scanner.take(&',');
+
+            digits(scanner)
+        };
+
+        assert_eq!(
+            scanner.tokenize(rule),
+            Ok(vec![
+                Spanned {
+                    value: 12,
+                    start: Position {
+                        offset: 0,
+                        line: 1,
+                        column: 1
+                    },
+                    end: Position {
+                        offset: 2,
+                        line: 1,
+                        column: 3
+                    }
+                },
+                Spanned {
+                    value: 34,
+                    start: Position {
+                        offset: 2,
+                        line: 1,
+                        column: 3
+                    },
+                    end: Position {
+                        offset: 5,
+                        line: 1,
+                        column: 6
+                    }
+                }
+            ])
+        )
+    }
+
+    #[test]
+    fn propagates_error() {
+        let mut scanner = Scanner::new("1a");
+
+        let rule = |scanner: &mut Scanner| {
+            scanner.scan(|sequence| match sequence {
+                ['1'] => Some(Action::Require),
+                _ => None,
+            } as Option<Action<()>>)
+        };
+
+        assert_eq!(
+            scanner.tokenize(rule),
+            Err(Error::Character(Position {
+                offset: 1,
+                line: 1,
+                column: 2
+            }))
+        )
+    }
+}
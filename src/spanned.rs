@@ -0,0 +1,10 @@
+use crate::Position;
+
+/// A value produced by `Scanner::tokenize`, together with the span of the
+/// input it was scanned from.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub start: Position,
+    pub end: Position,
+}